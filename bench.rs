@@ -6,8 +6,8 @@ fn main() {
     // Big enough to see parallel speedups
     let n = 1024usize;
     let seed = 42u64;
-    let a = Matrix::random(n, n, seed);
-    let b = Matrix::random(n, n, seed + 1);
+    let a = Matrix::<f32>::random(n, n, seed);
+    let b = Matrix::<f32>::random(n, n, seed + 1);
 
     // Warm up
     let _ = gemm_parallel(&a, &b);
@@ -21,8 +21,8 @@ fn small_matrices_match() {
 #[test]
 fn random_matrix_consistency() {
     let n = 64;
-    let a = Matrix::random(n, n, 42);
-    let b = Matrix::random(n, n, 43);
+    let a = Matrix::<f32>::random(n, n, 42);
+    let b = Matrix::<f32>::random(n, n, 43);
 
     let c_naive = gemm_naive(&a, &b);
     let c_par   = gemm_parallel(&a, &b);
@@ -3,70 +3,150 @@
 //!
 //! Safe, easy-to-follow blocked, parallel GEMM using Rayon.
 //!
-//! The code uses an `unsafe` slice creation when handing disjoint mutable slices
-//! of the output matrix `C` into Rayon threads. This is sound because the row
-//! blocks are non-overlapping. The inner micro-kernel (multiply_add_block_slice)
-//! is scalar and a clear hook to replace with SIMD intrinsics later.
+//! The GEMM path packs both operands into contiguous scratch buffers and runs a
+//! register-blocked `MR x NR` micro-kernel (`micro_kernel`) over them; that
+//! scalar micro-kernel is the single clear hook to replace with SIMD intrinsics
+//! later. Parallelism is across disjoint row-blocks of the output matrix `C`,
+//! handed out by `par_chunks_mut` so each Rayon thread owns a non-overlapping
+//! slice with no aliasing.
 
+use rayon::ThreadPool;
 use rayon::prelude::*;
-use std::ops::{Index, IndexMut};
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::ops::{Add, Index, IndexMut, Mul};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
 
+use num_traits::{One, Zero};
 use rand::Rng;
 use rand::SeedableRng;
+use rand::distributions::{Distribution, Standard};
 use rand::rngs::StdRng;
 
+/// Numeric scalar the engine can operate on.
+///
+/// A blanket impl covers every type that is `Copy` and supports the handful of
+/// arithmetic operations the blocked kernel needs, so `Matrix<f32>` and
+/// `Matrix<f64>` (and any user type meeting the bound) flow through the same
+/// code path. The `One`/`PartialEq` bounds let the `alpha`/`beta` scaling
+/// special-case the `0` and `1` multipliers.
+pub trait Scalar:
+    Copy + Zero + One + PartialEq + Add<Output = Self> + Mul<Output = Self>
+{
+}
+
+impl<T> Scalar for T where
+    T: Copy + Zero + One + PartialEq + Add<Output = T> + Mul<Output = T>
+{
+}
+
 #[derive(Clone, Debug)]
-pub struct Matrix {
+pub struct Matrix<T = f32> {
     pub rows: usize,
     pub cols: usize,
-    pub data: Vec<f32>, // row-major
+    pub data: Vec<T>, // row-major
 }
 
-impl Matrix {
+impl<T: Scalar> Matrix<T> {
     pub fn new(rows: usize, cols: usize) -> Self {
-        let data = vec![0.0_f32; rows * cols];
+        let data = vec![T::zero(); rows * cols];
         Self { rows, cols, data }
     }
 
-    pub fn from_fill(rows: usize, cols: usize, value: f32) -> Self {
+    pub fn from_fill(rows: usize, cols: usize, value: T) -> Self {
         let data = vec![value; rows * cols];
         Self { rows, cols, data }
     }
 
-    pub fn from_vec(rows: usize, cols: usize, data: Vec<f32>) -> Self {
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<T>) -> Self {
         assert_eq!(data.len(), rows * cols);
         Self { rows, cols, data }
     }
 
+    #[inline(always)]
+    pub fn get(&self, r: usize, c: usize) -> T {
+        self.data[r * self.cols + c]
+    }
+
+    #[inline(always)]
+    pub fn set(&mut self, r: usize, c: usize, val: T) {
+        self.data[r * self.cols + c] = val;
+    }
+
+    /// Borrow the whole matrix as a row-major [`MatrixView`] (`row_stride = cols`,
+    /// `col_stride = 1`).
+    pub fn view(&self) -> MatrixView<'_, T> {
+        MatrixView {
+            data: &self.data,
+            rows: self.rows,
+            cols: self.cols,
+            row_stride: self.cols,
+            col_stride: 1,
+        }
+    }
+}
+
+impl<T: Scalar> Matrix<T>
+where
+    Standard: Distribution<T>,
+{
     pub fn random(rows: usize, cols: usize, seed: u64) -> Self {
         let mut rng = StdRng::seed_from_u64(seed);
         let mut data = Vec::with_capacity(rows * cols);
         for _ in 0..rows * cols {
-            data.push(rng.gen::<f32>());
+            data.push(rng.gen::<T>());
         }
         Self { rows, cols, data }
     }
+}
 
+/// A non-owning, strided view over a matrix's elements.
+///
+/// Element `(r, c)` lives at `data[r * row_stride + c * col_stride]`, so a view
+/// can describe a transpose (swap the strides and dims) or a submatrix slice
+/// without copying. [`MatrixView::transpose`] is therefore free — it only swaps
+/// the strides and the row/column counts.
+#[derive(Clone, Copy, Debug)]
+pub struct MatrixView<'a, T = f32> {
+    pub data: &'a [T],
+    pub rows: usize,
+    pub cols: usize,
+    pub row_stride: usize,
+    pub col_stride: usize,
+}
+
+impl<'a, T: Scalar> MatrixView<'a, T> {
+    /// The transpose of this view, obtained by swapping the dimensions and
+    /// strides. No data is moved.
     #[inline(always)]
-    pub fn get(&self, r: usize, c: usize) -> f32 {
-        self.data[r * self.cols + c]
+    pub fn transpose(self) -> MatrixView<'a, T> {
+        MatrixView {
+            data: self.data,
+            rows: self.cols,
+            cols: self.rows,
+            row_stride: self.col_stride,
+            col_stride: self.row_stride,
+        }
     }
 
     #[inline(always)]
-    pub fn set(&mut self, r: usize, c: usize, val: f32) {
-        self.data[r * self.cols + c] = val;
+    pub fn get(&self, r: usize, c: usize) -> T {
+        self.data[r * self.row_stride + c * self.col_stride]
     }
 }
 
-impl Index<(usize, usize)> for Matrix {
-    type Output = f32;
+impl<T: Scalar> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
     fn index(&self, idx: (usize, usize)) -> &Self::Output {
         let (r, c) = idx;
         &self.data[r * self.cols + c]
     }
 }
 
-impl IndexMut<(usize, usize)> for Matrix {
+impl<T: Scalar> IndexMut<(usize, usize)> for Matrix<T> {
     fn index_mut(&mut self, idx: (usize, usize)) -> &mut Self::Output {
         let (r, c) = idx;
         &mut self.data[r * self.cols + c]
@@ -74,7 +154,7 @@ impl IndexMut<(usize, usize)> for Matrix {
 }
 
 /// Naive GEMM for correctness checks
-pub fn gemm_naive(a: &Matrix, b: &Matrix) -> Matrix {
+pub fn gemm_naive<T: Scalar>(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
     assert_eq!(a.cols, b.rows);
     let m = a.rows;
     let n = b.cols;
@@ -82,9 +162,9 @@ pub fn gemm_naive(a: &Matrix, b: &Matrix) -> Matrix {
     let mut c = Matrix::new(m, n);
     for i in 0..m {
         for j in 0..n {
-            let mut sum = 0.0_f32;
+            let mut sum = T::zero();
             for kk in 0..k {
-                sum += a.get(i, kk) * b.get(kk, j);
+                sum = sum + a.get(i, kk) * b.get(kk, j);
             }
             c.set(i, j, sum);
         }
@@ -92,94 +172,703 @@ pub fn gemm_naive(a: &Matrix, b: &Matrix) -> Matrix {
     c
 }
 
-/// Blocked, parallel GEMM (C = A * B)
+/// Blocked, parallel GEMM (`C = A * B`).
 ///
-/// This parallelizes across disjoint row-blocks of C and constructs
-/// a mutable slice for each block. That slice is non-overlapping, so
-/// using `unsafe` to create `&mut [f32]` from a raw pointer is sound.
-pub fn gemm_parallel(a: &Matrix, b: &Matrix) -> Matrix {
+/// A thin wrapper over [`sgemm_view`] / [`gemm_packed`] with `alpha = 1`,
+/// `beta = 0`; see [`gemm_packed`] for the packed, row-block-parallel kernel.
+pub fn gemm_parallel<T: Scalar + Send + Sync>(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
     assert_eq!(a.cols, b.rows);
+    let mut c = Matrix::new(a.rows, b.cols);
+    sgemm_view(T::one(), a.view(), b.view(), T::zero(), &mut c);
+    c
+}
+
+/// Tunable knobs for the blocked, parallel GEMM.
+///
+/// `block_m`/`block_n`/`block_k` are the cache-block sizes (`mc`/`nc`/`kc`); the
+/// register-blocked micro-kernel shape (`MR x NR`) is fixed. Threads come from
+/// Rayon's global pool by default, or from an explicit `pool`, or from a
+/// throwaway pool of `num_threads` workers. Problems whose largest dimension is
+/// below `serial_threshold` skip the parallel machinery and fall back to the
+/// naive triple loop, which wins on tiny matrices.
+#[derive(Clone)]
+pub struct GemmConfig {
+    pub block_m: usize,
+    pub block_n: usize,
+    pub block_k: usize,
+    /// Build a throwaway pool with this many threads. Ignored when `pool` is set.
+    pub num_threads: Option<usize>,
+    /// Run on this explicit pool instead of Rayon's global one.
+    pub pool: Option<Arc<ThreadPool>>,
+    /// Largest dimension below which the problem runs serially.
+    pub serial_threshold: usize,
+}
+
+impl Default for GemmConfig {
+    fn default() -> Self {
+        Self {
+            block_m: MC,
+            block_n: NC,
+            block_k: KC,
+            num_threads: None,
+            pool: None,
+            serial_threshold: 64,
+        }
+    }
+}
+
+/// Blocked, parallel GEMM (`C = A * B`) driven by an explicit [`GemmConfig`].
+///
+/// Like [`gemm_parallel`] but lets the caller pin the thread pool and tune the
+/// cache-block sizes per machine, and runs serially below the configured
+/// problem-size threshold.
+pub fn gemm_parallel_with<T: Scalar + Send + Sync>(
+    a: &Matrix<T>,
+    b: &Matrix<T>,
+    config: &GemmConfig,
+) -> Matrix<T> {
+    assert_eq!(a.cols, b.rows);
+
     let m = a.rows;
     let n = b.cols;
     let k = a.cols;
 
-    let block_m = 64usize.min(m).max(16);
-    let block_n = 64usize.min(n).max(16);
-    let block_k = 64usize.min(k).max(16);
+    // Below the threshold, the parallel/packing overhead outweighs the work.
+    if m.max(n).max(k) < config.serial_threshold {
+        return gemm_naive(a, b);
+    }
 
     let mut c = Matrix::new(m, n);
+    {
+        let mut run = || {
+            gemm_packed(
+                T::one(),
+                a.view(),
+                b.view(),
+                T::zero(),
+                &mut c,
+                config.block_m,
+                config.block_n,
+                config.block_k,
+            );
+        };
 
-    // Split C into mutable row-blocks *safely* before parallel work
-    let row_blocks: Vec<(usize, usize, &mut [f32])> = {
-        let ptr = c.data.as_mut_slice();
-        (0..m)
-            .step_by(block_m)
-            .map(|ri| {
-                let r_end = (ri + block_m).min(m);
-                let start = ri * n;
-                let end = r_end * n;
-                (ri, r_end, &mut ptr[start..end])
-            })
-            .collect()
-    };
+        if let Some(pool) = &config.pool {
+            pool.install(run);
+        } else if let Some(num_threads) = config.num_threads {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .expect("failed to build Rayon thread pool");
+            pool.install(run);
+        } else {
+            run();
+        }
+    }
+    c
+}
+
+/// Full BLAS-style GEMM: `C ← α·A·B + β·C`.
+///
+/// Unlike [`gemm_parallel`], which always returns a fresh `C = A * B`, this
+/// scales an existing output in place so callers can fuse scaling and
+/// accumulation without an extra pass over memory. Two values of `beta` are
+/// special-cased: `beta == 0.0` skips reading `C` entirely (so `c` need not be
+/// pre-zeroed), and `beta == 1.0` is a pure accumulate. Each freshly computed
+/// block is multiplied by `alpha` before being added into `C`.
+pub fn sgemm<T: Scalar + Send + Sync>(
+    alpha: T,
+    a: &Matrix<T>,
+    b: &Matrix<T>,
+    beta: T,
+    c: &mut Matrix<T>,
+) {
+    sgemm_view(alpha, a.view(), b.view(), beta, c);
+}
 
-    row_blocks.into_par_iter().for_each(|(ri, r_end, c_slice)| {
-        for cj in (0..n).step_by(block_n) {
-            let c_j_end = (cj + block_n).min(n);
-            for kk in (0..k).step_by(block_k) {
-                let k_end = (kk + block_k).min(k);
-                multiply_add_block_slice(
-                    a,
-                    b,
-                    c_slice,
-                    ri,
-                    r_end,
-                    cj,
-                    c_j_end,
-                    kk,
-                    k_end,
-                    n,
-                );
+/// `C ← α·A·B + β·C` where `A` and `B` are given as [`MatrixView`]s.
+///
+/// Because the operands are strided views, `A` and/or `B` may be transposed
+/// (via [`MatrixView::transpose`]) or sub-sliced with no allocation: to form
+/// `Aᵀ·B` the caller simply passes `a.view().transpose()`. The `alpha`/`beta`
+/// handling matches [`sgemm`].
+pub fn sgemm_view<T: Scalar + Send + Sync>(
+    alpha: T,
+    a: MatrixView<'_, T>,
+    b: MatrixView<'_, T>,
+    beta: T,
+    c: &mut Matrix<T>,
+) {
+    gemm_packed(alpha, a, b, beta, c, MC, NC, KC);
+}
+
+/// Register-blocked micro-kernel shape. The micro-kernel computes a fixed
+/// `MR x NR` tile of `C` at a time from packed operands, and is the single
+/// point a SIMD implementation would replace.
+const MR: usize = 8;
+const NR: usize = 8;
+
+/// Default cache-block sizes (`mc`/`nc` over M/N, `kc` over K). These bound the
+/// packed scratch buffers so a panel of A and a panel of B stay resident across
+/// the micro-kernel sweep; [`GemmConfig`] lets callers override them per machine.
+const MC: usize = 64;
+const NC: usize = 64;
+const KC: usize = 256;
+
+/// A single (`kc` × `nc`) slab of B, already packed into `NR`-column panels and
+/// stored at `offset` within the shared `b_pack` buffer.
+struct BPanel {
+    jc: usize,
+    nc: usize,
+    pc: usize,
+    kc: usize,
+    offset: usize,
+}
+
+/// Core packed GEMM worker: `C ← α·A·B + β·C` with caller-chosen cache-block
+/// sizes, following the standard GEBP structure.
+///
+/// B is packed exactly once up front into `NR`-column panels and shared across
+/// every M row-block; each row-block then packs its own (mc × kc) slab of A into
+/// `MR`-row panels and runs the register-blocked micro-kernel over the packed
+/// buffers. Packed buffers are zero-padded up to the `MR`/`NR` multiple so the
+/// micro-kernel always runs the full fixed shape and only the valid region is
+/// written back.
+///
+/// Parallelism stays across the outer `bm`-row blocks of `C`: `par_chunks_mut`
+/// hands each Rayon thread a disjoint, non-overlapping `bm`-row slice of `C`, so
+/// no aliasing or `unsafe` is needed.
+#[allow(clippy::too_many_arguments)]
+fn gemm_packed<T: Scalar + Send + Sync>(
+    alpha: T,
+    a: MatrixView<'_, T>,
+    b: MatrixView<'_, T>,
+    beta: T,
+    c: &mut Matrix<T>,
+    bm: usize,
+    bn: usize,
+    bk: usize,
+) {
+    assert_eq!(a.cols, b.rows);
+    assert_eq!(c.rows, a.rows);
+    assert_eq!(c.cols, b.cols);
+
+    let n = b.cols;
+    let k = a.cols;
+
+    // Apply the `beta` scaling to `C` up front. `beta == 0` means the prior
+    // contents are ignored (and need not have been initialized), `beta == 1`
+    // leaves them untouched for a pure accumulate.
+    if beta == T::zero() {
+        c.data.iter_mut().for_each(|x| *x = T::zero());
+    } else if beta != T::one() {
+        c.data.iter_mut().for_each(|x| *x = *x * beta);
+    }
+
+    // Pack all of B once into `NR`-column panels, one slab per (jc, pc) block,
+    // and record where each slab lands so every M row-block can reuse it.
+    let mut b_pack: Vec<T> = Vec::new();
+    let mut panels: Vec<BPanel> = Vec::new();
+    for jc in (0..n).step_by(bn) {
+        let nc = (jc + bn).min(n) - jc;
+        let nc_pad = nc.div_ceil(NR) * NR;
+        for pc in (0..k).step_by(bk) {
+            let kc = (pc + bk).min(k) - pc;
+            let offset = b_pack.len();
+            b_pack.resize(offset + kc * nc_pad, T::zero());
+            pack_b(b, &mut b_pack[offset..], pc, kc, jc, nc, nc_pad);
+            panels.push(BPanel { jc, nc, pc, kc, offset });
+        }
+    }
+
+    // Parallelize across disjoint `bm`-row blocks of C; each reuses the shared
+    // packed B and packs only its own A slab.
+    c.data
+        .par_chunks_mut(bm * n)
+        .enumerate()
+        .for_each(|(blk, c_slice)| {
+            let ri = blk * bm;
+            let mc = c_slice.len() / n;
+            let mc_pad = mc.div_ceil(MR) * MR;
+            let mut a_pack = vec![T::zero(); mc_pad * bk];
+
+            for panel in &panels {
+                let kc = panel.kc;
+                pack_a(a, &mut a_pack, ri, mc, mc_pad, panel.pc, kc);
+                let b_slab = &b_pack[panel.offset..];
+
+                // Macro-kernel: sweep the packed panels in MR x NR tiles.
+                for jr in (0..panel.nc).step_by(NR) {
+                    let nr = (jr + NR).min(panel.nc) - jr;
+                    let b_panel = &b_slab[jr * kc..jr * kc + NR * kc];
+                    for ir in (0..mc).step_by(MR) {
+                        let mr = (ir + MR).min(mc) - ir;
+                        let a_panel = &a_pack[ir * kc..ir * kc + MR * kc];
+                        micro_kernel(
+                            a_panel,
+                            b_panel,
+                            c_slice,
+                            alpha,
+                            kc,
+                            ir,
+                            panel.jc + jr,
+                            mr,
+                            nr,
+                            n,
+                        );
+                    }
+                }
+            }
+        });
+}
+
+/// Pack the (mc × kc) slab `A[ri.., pc..]` into `MR`-row panels: element
+/// `(row, l)` of the slab lands at `panel*MR*kc + l*MR + (row - panel*MR)`, so
+/// the micro-kernel reads `MR` consecutive elements per K step. Rows past `mc`
+/// within the final panel are left zero.
+fn pack_a<T: Scalar>(
+    a: MatrixView<'_, T>,
+    a_pack: &mut [T],
+    ri: usize,
+    mc: usize,
+    mc_pad: usize,
+    pc: usize,
+    kc: usize,
+) {
+    let (rsa, csa) = (a.row_stride, a.col_stride);
+    // `MR`-row panels, column-major within a panel.
+    for panel in 0..(mc_pad / MR) {
+        let base = panel * MR * kc;
+        for l in 0..kc {
+            for r in 0..MR {
+                let row = panel * MR + r;
+                let val = if row < mc {
+                    a.data[(ri + row) * rsa + (pc + l) * csa]
+                } else {
+                    T::zero()
+                };
+                a_pack[base + l * MR + r] = val;
             }
         }
-    });
+    }
+}
 
-    c
+/// Pack the (kc × nc) slab `B[pc.., jc..]` into `NR`-column panels: element
+/// `(l, col)` of the slab lands at `panel*NR*kc + l*NR + (col - panel*NR)`.
+/// Columns past `nc` within the final panel are left zero.
+fn pack_b<T: Scalar>(
+    b: MatrixView<'_, T>,
+    b_pack: &mut [T],
+    pc: usize,
+    kc: usize,
+    jc: usize,
+    nc: usize,
+    nc_pad: usize,
+) {
+    let (rsb, csb) = (b.row_stride, b.col_stride);
+    for panel in 0..(nc_pad / NR) {
+        let base = panel * NR * kc;
+        for l in 0..kc {
+            for c in 0..NR {
+                let col = panel * NR + c;
+                let val = if col < nc {
+                    b.data[(pc + l) * rsb + (jc + col) * csb]
+                } else {
+                    T::zero()
+                };
+                b_pack[base + l * NR + c] = val;
+            }
+        }
+    }
 }
 
-/// Inner kernel operating on a mutable slice that corresponds to rows [r0..r1) of C.
-/// The `c_slice` has shape ((r1-r0) x n) stored row-major, and `n` is the full C stride (cols).
+/// Register-blocked micro-kernel: accumulate one `MR x NR` tile over the packed
+/// K panel into a register tile, then add `alpha *` the valid region into `C`.
+/// `a_panel` holds `MR` consecutive elements per K step and `b_panel` holds
+/// `NR`, so both are read sequentially. This is the single SIMD replacement
+/// point.
 #[inline(always)]
-fn multiply_add_block_slice(
-    a: &Matrix,
-    b: &Matrix,
-    c_slice: &mut [f32],
-    r0: usize,
-    r1: usize,
-    c0: usize,
-    c1: usize,
-    k0: usize,
-    k1: usize,
-    n: usize, // stride of C (number of columns)
+#[allow(clippy::too_many_arguments)]
+fn micro_kernel<T: Scalar>(
+    a_panel: &[T],
+    b_panel: &[T],
+    c_slice: &mut [T],
+    alpha: T,
+    kc: usize,
+    ci: usize, // tile's first row, relative to the C row-block
+    cj: usize, // tile's first column in C
+    mr: usize, // valid rows (<= MR)
+    nr: usize, // valid cols (<= NR)
+    n: usize,  // stride of C
 ) {
-    let a_cols = a.cols;
-    let b_cols = b.cols;
+    let mut acc = [T::zero(); MR * NR];
+    for l in 0..kc {
+        let a_off = l * MR;
+        let b_off = l * NR;
+        for r in 0..MR {
+            let a_rl = a_panel[a_off + r];
+            let row = r * NR;
+            for c in 0..NR {
+                acc[row + c] = acc[row + c] + a_rl * b_panel[b_off + c];
+            }
+        }
+    }
+
+    for r in 0..mr {
+        let c_row_offset = (ci + r) * n;
+        let acc_row = r * NR;
+        for c in 0..nr {
+            let idx = c_row_offset + cj + c;
+            c_slice[idx] = c_slice[idx] + alpha * acc[acc_row + c];
+        }
+    }
+}
+
+/// Compressed sparse row (CSR) matrix.
+///
+/// Only the non-zero entries are stored: `values[p]` sits at column
+/// `col_indices[p]`, and `row_ptr[i]..row_ptr[i + 1]` is the half-open range of
+/// `p` for row `i` (so `row_ptr` has length `rows + 1` and `row_ptr[rows]` is the
+/// total number of stored entries). This is the natural form for operands that
+/// are mostly zeros, where dense GEMM wastes nearly all its FLOPs.
+#[derive(Clone, Debug)]
+pub struct CsrMatrix<T = f32> {
+    pub rows: usize,
+    pub cols: usize,
+    pub values: Vec<T>,
+    pub col_indices: Vec<usize>,
+    pub row_ptr: Vec<usize>, // length rows + 1
+}
+
+impl<T: Scalar> CsrMatrix<T> {
+    /// Number of stored (non-zero) entries.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+}
 
-    // local_i is index into c_slice rows
-    for i in r0..r1 {
-        let local_i = i - r0;
-        let c_row_offset = local_i * n;
-        for kk in k0..k1 {
-            let a_ik = a.data[i * a_cols + kk];
-            let b_row_offset = kk * b_cols;
-            for j in c0..c1 {
-                // c_slice index is (local_i * n + j)
-                c_slice[c_row_offset + j] += a_ik * b.data[b_row_offset + j];
+impl<T: Scalar> Matrix<T> {
+    /// Build a [`CsrMatrix`] from this dense matrix, dropping every entry whose
+    /// magnitude does not exceed `threshold` (pass `T::zero()` to keep only the
+    /// structurally non-zero values).
+    pub fn to_csr(&self, threshold: T) -> CsrMatrix<T>
+    where
+        T: num_traits::Signed + PartialOrd,
+    {
+        let mut values = Vec::new();
+        let mut col_indices = Vec::new();
+        let mut row_ptr = Vec::with_capacity(self.rows + 1);
+        row_ptr.push(0);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                let v = self.get(i, j);
+                if v.abs() > threshold {
+                    values.push(v);
+                    col_indices.push(j);
+                }
             }
+            row_ptr.push(values.len());
+        }
+        CsrMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            values,
+            col_indices,
+            row_ptr,
         }
     }
 }
 
+/// Sparse × dense multiply: `C = S · B` with `S` in CSR form and `B` dense.
+///
+/// For each row `i`, every stored `(col, value)` of `S` scales row `col` of `B`
+/// into row `i` of `C`. Parallelism stays across disjoint output row-blocks,
+/// exactly like the dense [`gemm_parallel`] path.
+pub fn spmm<T: Scalar + Send + Sync>(s: &CsrMatrix<T>, b: &Matrix<T>) -> Matrix<T> {
+    assert_eq!(s.cols, b.rows);
+    let m = s.rows;
+    let n = b.cols;
+    let mut c = Matrix::new(m, n);
+
+    // Disjoint mutable row-blocks of C (`MC` rows, i.e. `MC * n` elements each),
+    // handed out safely by `par_chunks_mut`.
+    c.data
+        .par_chunks_mut(MC * n)
+        .enumerate()
+        .for_each(|(blk, c_slice)| {
+            let ri = blk * MC;
+            let rows_here = c_slice.len() / n;
+            for local in 0..rows_here {
+                let i = ri + local;
+                let c_row_offset = local * n;
+                for p in s.row_ptr[i]..s.row_ptr[i + 1] {
+                    let val = s.values[p];
+                    let b_row_offset = s.col_indices[p] * b.cols;
+                    for j in 0..n {
+                        let idx = c_row_offset + j;
+                        c_slice[idx] = c_slice[idx] + val * b.data[b_row_offset + j];
+                    }
+                }
+            }
+        });
+
+    c
+}
+
+/// `y ← α·A·x + β·y` (BLAS level-2 general matrix-vector product).
+///
+/// Like [`gemm_parallel`], the work is split across disjoint row-blocks of the
+/// output `y`, so large matrix-vector products scale across cores. `beta == 0`
+/// skips reading the prior `y`.
+pub fn gemv<T: Scalar + Send + Sync>(alpha: T, a: &Matrix<T>, x: &[T], beta: T, y: &mut [T]) {
+    assert_eq!(a.cols, x.len());
+    assert_eq!(a.rows, y.len());
+
+    let cols = a.cols;
+    let a_data = &a.data;
+    let beta_is_zero = beta == T::zero();
+
+    y.par_chunks_mut(MC).enumerate().for_each(|(blk, y_chunk)| {
+        let row0 = blk * MC;
+        for (local, yi) in y_chunk.iter_mut().enumerate() {
+            let base = (row0 + local) * cols;
+            let mut sum = T::zero();
+            for j in 0..cols {
+                sum = sum + a_data[base + j] * x[j];
+            }
+            let scaled = alpha * sum;
+            *yi = if beta_is_zero { scaled } else { scaled + beta * *yi };
+        }
+    });
+}
+
+/// Dot product `xᵀ·y` (BLAS level-1).
+pub fn dot<T: Scalar>(x: &[T], y: &[T]) -> T {
+    assert_eq!(x.len(), y.len());
+    let mut acc = T::zero();
+    for i in 0..x.len() {
+        acc = acc + x[i] * y[i];
+    }
+    acc
+}
+
+/// `y ← α·x + y` (BLAS level-1).
+pub fn axpy<T: Scalar>(alpha: T, x: &[T], y: &mut [T]) {
+    assert_eq!(x.len(), y.len());
+    for i in 0..x.len() {
+        y[i] = alpha * x[i] + y[i];
+    }
+}
+
+/// Index of the first element of largest magnitude (BLAS `i*amax`).
+///
+/// Scans every entry and tracks the running maximum of `abs(val)`; returns `0`
+/// for an empty slice. Pass `matrix.data.as_slice()` to scan a whole matrix.
+pub fn iamax<T>(x: &[T]) -> usize
+where
+    T: Scalar + num_traits::Signed + PartialOrd,
+{
+    let mut idx = 0;
+    let mut max = match x.first() {
+        Some(v) => v.abs(),
+        None => return 0,
+    };
+    for (i, v) in x.iter().enumerate().skip(1) {
+        let mag = v.abs();
+        if mag > max {
+            max = mag;
+            idx = i;
+        }
+    }
+    idx
+}
+
+/// Build an `InvalidData` I/O error from a message.
+fn invalid_data<E: Display>(msg: &str, err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{msg}: {err}"))
+}
+
+fn malformed(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+impl<T> Matrix<T>
+where
+    T: Scalar + FromStr + Display,
+    <T as FromStr>::Err: Display,
+{
+    /// Read a matrix from a [Matrix Market](https://math.nist.gov/MatrixMarket/formats.html)
+    /// file (`array` or `coordinate`, `real general`).
+    ///
+    /// `array` files store dense column-major values; `coordinate` files store
+    /// 1-based `i j value` triplets. Lines beginning with `%` after the banner
+    /// are treated as comments.
+    pub fn read_matrix_market<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let mut lines = reader.lines();
+
+        let banner = lines
+            .next()
+            .ok_or_else(|| malformed("empty Matrix Market file"))??;
+        let banner_lower = banner.to_ascii_lowercase();
+        if !banner_lower.starts_with("%%matrixmarket") {
+            return Err(malformed("missing %%MatrixMarket banner"));
+        }
+        let coordinate = if banner_lower.contains("coordinate") {
+            true
+        } else if banner_lower.contains("array") {
+            false
+        } else {
+            return Err(malformed("unsupported Matrix Market format"));
+        };
+
+        // First non-comment, non-blank line is the size line.
+        let size_line = loop {
+            let line = lines
+                .next()
+                .ok_or_else(|| malformed("missing size line"))??;
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with('%') {
+                break trimmed.to_string();
+            }
+        };
+        let mut dims = size_line.split_whitespace();
+        let rows = parse_field(dims.next(), "rows")?;
+        let cols = parse_field(dims.next(), "cols")?;
+
+        let mut m = Matrix::new(rows, cols);
+        if coordinate {
+            let nnz: usize = parse_field(dims.next(), "nnz")?;
+            let mut seen = 0;
+            for line in lines {
+                let line = line?;
+                let trimmed = line.trim();
+                if trimmed.is_empty() || trimmed.starts_with('%') {
+                    continue;
+                }
+                let mut it = trimmed.split_whitespace();
+                let i: usize = parse_field(it.next(), "row index")?;
+                let j: usize = parse_field(it.next(), "col index")?;
+                let v: T = parse_field(it.next(), "value")?;
+                if i < 1 || i > rows || j < 1 || j > cols {
+                    return Err(malformed("coordinate index out of bounds"));
+                }
+                m[(i - 1, j - 1)] = v;
+                seen += 1;
+            }
+            if seen != nnz {
+                return Err(malformed("entry count does not match nnz"));
+            }
+        } else {
+            // Dense, column-major.
+            let mut values = Vec::with_capacity(rows * cols);
+            for line in lines {
+                let line = line?;
+                let trimmed = line.trim();
+                if trimmed.is_empty() || trimmed.starts_with('%') {
+                    continue;
+                }
+                for tok in trimmed.split_whitespace() {
+                    values.push(parse_value::<T>(tok)?);
+                }
+            }
+            if values.len() != rows * cols {
+                return Err(malformed("value count does not match dimensions"));
+            }
+            let mut idx = 0;
+            for c in 0..cols {
+                for r in 0..rows {
+                    m[(r, c)] = values[idx];
+                    idx += 1;
+                }
+            }
+        }
+        Ok(m)
+    }
+
+    /// Write this matrix as a dense Matrix Market `array real general` file.
+    pub fn write_matrix_market<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = File::create(path)?;
+        let mut w = BufWriter::new(file);
+        writeln!(w, "%%MatrixMarket matrix array real general")?;
+        writeln!(w, "{} {}", self.rows, self.cols)?;
+        for c in 0..self.cols {
+            for r in 0..self.rows {
+                writeln!(w, "{}", self.get(r, c))?;
+            }
+        }
+        w.flush()
+    }
+
+    /// Read a matrix from a delimited (comma-separated) file, one matrix row per
+    /// line. The number of columns is taken from the first row.
+    pub fn read_csv<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+
+        let mut data: Vec<T> = Vec::new();
+        let mut rows = 0usize;
+        let mut cols = 0usize;
+        for line in reader.lines() {
+            let line = line?;
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let mut row_cols = 0usize;
+            for tok in trimmed.split(',') {
+                data.push(parse_value::<T>(tok.trim())?);
+                row_cols += 1;
+            }
+            if rows == 0 {
+                cols = row_cols;
+            } else if row_cols != cols {
+                return Err(malformed("inconsistent column count in CSV"));
+            }
+            rows += 1;
+        }
+        Ok(Matrix::from_vec(rows, cols, data))
+    }
+
+    /// Write this matrix as a comma-separated file, one matrix row per line.
+    pub fn write_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = File::create(path)?;
+        let mut w = BufWriter::new(file);
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                if c > 0 {
+                    write!(w, ",")?;
+                }
+                write!(w, "{}", self.get(r, c))?;
+            }
+            writeln!(w)?;
+        }
+        w.flush()
+    }
+}
+
+/// Parse a whitespace/line token into `T`, mapping failures to `InvalidData`.
+fn parse_value<T>(tok: &str) -> io::Result<T>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+{
+    tok.parse::<T>()
+        .map_err(|e| invalid_data("could not parse value", e))
+}
+
+/// Parse an optional, named field, erroring if it is missing or malformed.
+fn parse_field<V>(tok: Option<&str>, name: &str) -> io::Result<V>
+where
+    V: FromStr,
+    <V as FromStr>::Err: Display,
+{
+    let tok = tok.ok_or_else(|| malformed(&format!("missing {name}")))?;
+    tok.parse::<V>()
+        .map_err(|e| invalid_data(&format!("could not parse {name}"), e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,8 +901,8 @@ mod tests {
     #[test]
     fn random_matrix_consistency() {
         let n = 64;
-        let a = Matrix::random(n, n, 42);
-        let b = Matrix::random(n, n, 43);
+        let a = Matrix::<f32>::random(n, n, 42);
+        let b = Matrix::<f32>::random(n, n, 43);
 
         let c_naive = gemm_naive(&a, &b);
         let c_par = gemm_parallel(&a, &b);
@@ -224,4 +913,243 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn f64_matches_naive_tight_epsilon() {
+        // Double precision runs the same blocked/parallel path; the tighter
+        // epsilon exercises the accuracy we can only get at `f64`.
+        let n = 64;
+        let a = Matrix::<f64>::random(n, n, 42);
+        let b = Matrix::<f64>::random(n, n, 43);
+
+        let c_naive = gemm_naive(&a, &b);
+        let c_par = gemm_parallel(&a, &b);
+
+        for i in 0..n {
+            for j in 0..n {
+                assert_abs_diff_eq!(c_naive[(i, j)], c_par[(i, j)], epsilon = 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn sgemm_alpha_beta() {
+        let n = 48;
+        let a = Matrix::random(n, n, 7);
+        let b = Matrix::random(n, n, 8);
+        let c0 = Matrix::random(n, n, 9);
+
+        let alpha = 2.0_f32;
+        let beta = 3.0_f32;
+
+        // Reference: alpha * (A*B) + beta * C0
+        let ab = gemm_naive(&a, &b);
+        let mut expected = c0.clone();
+        for i in 0..n {
+            for j in 0..n {
+                expected[(i, j)] = alpha * ab[(i, j)] + beta * c0[(i, j)];
+            }
+        }
+
+        let mut c = c0.clone();
+        sgemm(alpha, &a, &b, beta, &mut c);
+        for i in 0..n {
+            for j in 0..n {
+                assert_abs_diff_eq!(c[(i, j)], expected[(i, j)], epsilon = 1e-3);
+            }
+        }
+
+        // beta == 0.0 ignores the prior contents of C.
+        let mut c_fresh = Matrix::from_fill(n, n, 123.0);
+        sgemm(1.0, &a, &b, 0.0, &mut c_fresh);
+        for i in 0..n {
+            for j in 0..n {
+                assert_abs_diff_eq!(c_fresh[(i, j)], ab[(i, j)], epsilon = 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn transposed_view_matches_explicit_transpose() {
+        // A is stored (k x m); Aᵀ is (m x k) and Aᵀ·B should match multiplying
+        // an explicitly transposed copy.
+        let (m, k, n) = (40usize, 32usize, 24usize);
+        let a = Matrix::random(k, m, 11); // the operand we view transposed
+        let b = Matrix::random(k, n, 12);
+
+        // Explicit transpose of A into an (m x k) matrix.
+        let mut a_t = Matrix::new(m, k);
+        for i in 0..m {
+            for kk in 0..k {
+                a_t[(i, kk)] = a[(kk, i)];
+            }
+        }
+        let expected = gemm_naive(&a_t, &b);
+
+        let mut c = Matrix::new(m, n);
+        sgemm_view(1.0, a.view().transpose(), b.view(), 0.0, &mut c);
+        for i in 0..m {
+            for j in 0..n {
+                assert_abs_diff_eq!(c[(i, j)], expected[(i, j)], epsilon = 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn config_variant_matches_default() {
+        let n = 96;
+        let a = Matrix::<f32>::random(n, n, 1);
+        let b = Matrix::<f32>::random(n, n, 2);
+        let expected = gemm_naive(&a, &b);
+
+        // Custom block sizes + a pinned 2-thread pool.
+        let config = GemmConfig {
+            block_m: 32,
+            block_n: 48,
+            block_k: 80,
+            num_threads: Some(2),
+            serial_threshold: 16,
+            ..GemmConfig::default()
+        };
+        let c = gemm_parallel_with(&a, &b, &config);
+        for i in 0..n {
+            for j in 0..n {
+                assert_abs_diff_eq!(c[(i, j)], expected[(i, j)], epsilon = 1e-3);
+            }
+        }
+
+        // Tiny problems fall back to the serial path but stay correct.
+        let sa = Matrix::<f32>::random(8, 8, 3);
+        let sb = Matrix::<f32>::random(8, 8, 4);
+        let s_expected = gemm_naive(&sa, &sb);
+        let s = gemm_parallel_with(&sa, &sb, &GemmConfig::default());
+        for i in 0..8 {
+            for j in 0..8 {
+                assert_abs_diff_eq!(s[(i, j)], s_expected[(i, j)], epsilon = 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn spmm_matches_dense() {
+        // A mostly-zero matrix with a handful of explicit non-zeros.
+        let mut dense = Matrix::<f32>::new(70, 50);
+        dense[(0, 3)] = 1.5;
+        dense[(0, 49)] = -2.0;
+        dense[(5, 0)] = 4.0;
+        dense[(69, 25)] = 0.75;
+        dense[(42, 42)] = -1.0;
+
+        let b = Matrix::<f32>::random(50, 30, 99);
+
+        let csr = dense.to_csr(0.0);
+        assert_eq!(csr.nnz(), 5);
+
+        let sparse_c = spmm(&csr, &b);
+        let dense_c = gemm_naive(&dense, &b);
+        for i in 0..70 {
+            for j in 0..30 {
+                assert_abs_diff_eq!(sparse_c[(i, j)], dense_c[(i, j)], epsilon = 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn to_csr_threshold_drops_small_entries() {
+        let dense = Matrix::from_vec(2, 3, vec![0.01, 0.0, 5.0, -0.2, 3.0, -0.05]);
+        let csr = dense.to_csr(0.1);
+        // Only |v| > 0.1 survive: 5.0, -0.2, 3.0.
+        assert_eq!(csr.nnz(), 3);
+        assert_eq!(csr.col_indices, vec![2, 0, 1]);
+        assert_eq!(csr.row_ptr, vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn matrix_market_roundtrip() {
+        let a = Matrix::from_vec(2, 3, vec![1.0_f64, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let path = std::env::temp_dir().join("pme_mm_roundtrip.mtx");
+        a.write_matrix_market(&path).unwrap();
+        let b = Matrix::<f64>::read_matrix_market(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!((b.rows, b.cols), (2, 3));
+        for i in 0..2 {
+            for j in 0..3 {
+                assert_abs_diff_eq!(a[(i, j)], b[(i, j)], epsilon = 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn matrix_market_coordinate() {
+        let path = std::env::temp_dir().join("pme_mm_coord.mtx");
+        std::fs::write(
+            &path,
+            "%%MatrixMarket matrix coordinate real general\n% a comment\n3 3 2\n1 1 4.5\n3 2 -1.0\n",
+        )
+        .unwrap();
+        let m = Matrix::<f64>::read_matrix_market(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_abs_diff_eq!(m[(0, 0)], 4.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(m[(2, 1)], -1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(m[(1, 1)], 0.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn blas_level1_2_ops() {
+        // gemv against a hand-computed reference.
+        let a = Matrix::from_vec(3, 2, vec![1.0_f32, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let x = [1.0_f32, 1.0];
+        let mut y = [10.0_f32, 20.0, 30.0];
+        // y = 2 * A*x + 0.5 * y; A*x = [3, 7, 11]
+        gemv(2.0, &a, &x, 0.5, &mut y);
+        assert_abs_diff_eq!(y[0], 2.0 * 3.0 + 0.5 * 10.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(y[1], 2.0 * 7.0 + 0.5 * 20.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(y[2], 2.0 * 11.0 + 0.5 * 30.0, epsilon = 1e-5);
+
+        // dot
+        assert_abs_diff_eq!(dot(&[1.0_f32, 2.0, 3.0], &[4.0, 5.0, 6.0]), 32.0, epsilon = 1e-6);
+
+        // axpy: y = 3*x + y
+        let mut yv = [1.0_f32, 1.0, 1.0];
+        axpy(3.0, &[1.0_f32, 2.0, 3.0], &mut yv);
+        assert_eq!(yv, [4.0, 7.0, 10.0]);
+
+        // iamax picks the largest-magnitude entry (first on ties).
+        assert_eq!(iamax(&[0.5_f32, -3.0, 2.0, 3.0]), 1);
+        assert_eq!(iamax::<f32>(&[]), 0);
+    }
+
+    #[test]
+    fn gemv_matches_naive_large() {
+        let n = 200;
+        let a = Matrix::<f32>::random(n, n, 5);
+        let x = Matrix::<f32>::random(n, 1, 6).data;
+        let mut y = vec![0.0_f32; n];
+        gemv(1.0, &a, &x, 0.0, &mut y);
+        for i in 0..n {
+            let mut sum = 0.0_f32;
+            for j in 0..n {
+                sum += a[(i, j)] * x[j];
+            }
+            assert_abs_diff_eq!(y[i], sum, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn csv_roundtrip() {
+        let a = Matrix::from_vec(2, 2, vec![1.5_f64, -2.25, 3.0, 4.0]);
+        let path = std::env::temp_dir().join("pme_csv_roundtrip.csv");
+        a.write_csv(&path).unwrap();
+        let b = Matrix::<f64>::read_csv(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!((b.rows, b.cols), (2, 2));
+        for i in 0..2 {
+            for j in 0..2 {
+                assert_abs_diff_eq!(a[(i, j)], b[(i, j)], epsilon = 1e-12);
+            }
+        }
+    }
 }